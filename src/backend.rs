@@ -0,0 +1,19 @@
+use std::error;
+
+/// Abstracts over the different ways the backlight brightness of the active
+/// output can be queried and changed, so callers don't need to care whether
+/// they are talking to X via RandR or directly to a sysfs device.
+///
+/// The minimal backlight value is always assumed to be 0, matching the
+/// RandR backlight property convention, so implementations only need to
+/// expose the maximal value.
+pub trait Backend {
+    /// Returns the maximal absolute backlight value.
+    fn max(&self) -> Result<u32, Box<dyn error::Error>>;
+
+    /// Returns the current absolute backlight value.
+    fn current(&self) -> Result<u32, Box<dyn error::Error>>;
+
+    /// Sets the absolute backlight value.
+    fn set(&self, val: u32) -> Result<(), Box<dyn error::Error>>;
+}
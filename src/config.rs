@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::custom_errors;
+
+#[derive(Deserialize, Default)]
+struct RawConfig {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    presets: HashMap<String, String>,
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+}
+
+/// Presets and aliases loaded from `$XDG_CONFIG_HOME/rbacklight/config.toml`
+/// (and whatever it `include`s), used to resolve `--preset NAME`.
+pub struct Config {
+    presets: HashMap<String, String>,
+    aliases: HashMap<String, String>,
+}
+
+impl Config {
+    /// Loads the user's config file, if any. A missing config file is not an
+    /// error, it just means no presets/aliases are available.
+    pub fn load() -> Result<Config, Box<dyn error::Error>> {
+        let path = config_path();
+        let mut presets = HashMap::new();
+        let mut aliases = HashMap::new();
+
+        if path.exists() {
+            let mut visited = HashSet::new();
+            load_into(&path, &mut presets, &mut aliases, &mut visited)?;
+        }
+
+        Ok(Config { presets, aliases })
+    }
+
+    /// Resolves `name` to a preset value string (e.g. `"70%"`), following a
+    /// single level of alias indirection if `name` is itself an alias.
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        let preset_name = self.aliases.get(name).map(|s| s.as_str()).unwrap_or(name);
+        self.presets.get(preset_name).map(|s| s.as_str())
+    }
+}
+
+/// Recursively merges `path` and everything it `include`s into `presets`/
+/// `aliases`. `visited` guards against include cycles.
+fn load_into(
+    path: &Path,
+    presets: &mut HashMap<String, String>,
+    aliases: &mut HashMap<String, String>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<(), Box<dyn error::Error>> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let text = fs::read_to_string(path)?;
+    let raw: RawConfig = toml::from_str(&text)?;
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    for include in &raw.include {
+        load_into(&base_dir.join(include), presets, aliases, visited)?;
+    }
+
+    presets.extend(raw.presets);
+    aliases.extend(raw.aliases);
+    Ok(())
+}
+
+fn config_path() -> PathBuf {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").map_or_else(|| PathBuf::from("."), PathBuf::from);
+            home.join(".config")
+        });
+    config_home.join("rbacklight").join("config.toml")
+}
+
+/// Parses a preset value (e.g. `"70%"` or `"450"`) into the mode and value
+/// that `--preset` should behave as if they had been passed directly.
+pub fn parse_preset_value(value: &str) -> Result<(crate::Mode, u32), Box<dyn error::Error>> {
+    let invalid = || {
+        Box::new(custom_errors::InvalidPresetValueError {
+            value: value.to_string(),
+        })
+    };
+
+    if let Some(percent) = value.trim().strip_suffix('%') {
+        let val = percent.trim().parse::<u32>().map_err(|_| invalid())?;
+        Ok((crate::Mode::Relative, val))
+    } else {
+        let val = value.trim().parse::<u32>().map_err(|_| invalid())?;
+        Ok((crate::Mode::Absolute, val))
+    }
+}
@@ -1,14 +1,5 @@
 use std::{error, fmt};
 
-#[derive(Debug, Clone)]
-pub struct NoValidScreenResourceError;
-impl fmt::Display for NoValidScreenResourceError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "could not find valid screen resouce")
-    }
-}
-impl error::Error for NoValidScreenResourceError {}
-
 #[derive(Debug, Clone)]
 pub struct NoValidBacklightRangeValuesError;
 impl fmt::Display for NoValidBacklightRangeValuesError {
@@ -44,6 +35,104 @@ impl fmt::Display for ValueOutOfRangeError {
 }
 impl error::Error for ValueOutOfRangeError {}
 
+#[derive(Debug, Clone)]
+pub struct NoBacklightDeviceFoundError;
+impl fmt::Display for NoBacklightDeviceFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "could not find a backlight device under /sys/class/backlight")
+    }
+}
+impl error::Error for NoBacklightDeviceFoundError {}
+
+#[derive(Debug, Clone)]
+pub struct NoBacklightCapableOutputError;
+impl fmt::Display for NoBacklightCapableOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "none of the connected outputs expose a usable backlight property")
+    }
+}
+impl error::Error for NoBacklightCapableOutputError {}
+
+#[derive(Debug, Clone)]
+pub struct OutputNotFoundError {
+    pub name: String,
+}
+impl fmt::Display for OutputNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no backlight-capable output named '{}' was found",
+            self.name
+        )
+    }
+}
+impl error::Error for OutputNotFoundError {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidTimezoneError {
+    pub name: String,
+}
+impl fmt::Display for InvalidTimezoneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a valid IANA timezone name", self.name)
+    }
+}
+impl error::Error for InvalidTimezoneError {}
+
+#[derive(Debug, Clone)]
+pub struct PresetNotFoundError {
+    pub name: String,
+}
+impl fmt::Display for PresetNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no preset or alias named '{}' was found in the config", self.name)
+    }
+}
+impl error::Error for PresetNotFoundError {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidPresetValueError {
+    pub value: String,
+}
+impl fmt::Display for InvalidPresetValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "preset value '{}' is not a valid absolute or percentage value",
+            self.value
+        )
+    }
+}
+impl error::Error for InvalidPresetValueError {}
+
+#[derive(Debug, Clone)]
+pub struct DaemonBrightnessOutOfRangeError {
+    pub name: &'static str,
+    pub value: u32,
+}
+impl fmt::Display for DaemonBrightnessOutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "--{} must be a percentage between 0 and 100, got {}",
+            self.name, self.value
+        )
+    }
+}
+impl error::Error for DaemonBrightnessOutOfRangeError {}
+
+#[derive(Debug, Clone)]
+pub struct MissingDaemonArgumentsError;
+impl fmt::Display for MissingDaemonArgumentsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "--daemon requires --latitude, --longitude and --timezone to be set"
+        )
+    }
+}
+impl error::Error for MissingDaemonArgumentsError {}
+
 #[derive(Debug, Clone)]
 pub struct StepParameterOutOfRangeError {
     pub max: u32,
@@ -0,0 +1,216 @@
+use std::error;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::backend::Backend;
+use crate::custom_errors;
+
+/// Configuration for `daemon` mode: the two brightness levels to shift
+/// between, the observer's location/timezone used to compute sunrise and
+/// sunset, and how quickly/how often to react.
+pub struct DaemonConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: Tz,
+    /// Target brightness percentage (0-100) during the day.
+    pub day_percent: u32,
+    /// Target brightness percentage (0-100) during the night.
+    pub night_percent: u32,
+    /// How long before/after sunrise and sunset to linearly fade between
+    /// the day and night percentages.
+    pub transition: Duration,
+    pub poll_interval: StdDuration,
+}
+
+impl DaemonConfig {
+    pub fn parse_timezone(name: &str) -> Result<Tz, Box<dyn error::Error>> {
+        Tz::from_str(name)
+            .map_err(|_| Box::new(custom_errors::InvalidTimezoneError { name: name.to_string() }) as Box<dyn error::Error>)
+    }
+}
+
+/// Runs `daemon` mode: forever, on `config.poll_interval`, compute the
+/// brightness percentage for the current time and apply it via `backend`.
+pub fn run(
+    backend: &dyn Backend,
+    max_backlight: u32,
+    config: DaemonConfig,
+) -> Result<(), Box<dyn error::Error>> {
+    loop {
+        let now = Utc::now().with_timezone(&config.timezone);
+        let (sunrise, sunset) = solar_times(&config, now.date_naive());
+
+        let target_percent = target_brightness_percent(
+            now,
+            sunrise,
+            sunset,
+            config.transition,
+            config.day_percent,
+            config.night_percent,
+        );
+        let target_abs = crate::steps_to_absolute(max_backlight, 100, target_percent);
+        backend.set(target_abs)?;
+
+        thread::sleep(config.poll_interval);
+    }
+}
+
+/// Computes today's sunrise/sunset in `config.timezone` from the NOAA-style
+/// approximation: solar declination from the day-of-year, the hour angle
+/// from latitude and declination, and a longitude + UTC-offset correction
+/// from solar time to wall-clock time.
+fn solar_times(
+    config: &DaemonConfig,
+    date: chrono::NaiveDate,
+) -> (DateTime<Tz>, DateTime<Tz>) {
+    let day_of_year = date.ordinal() as f64;
+
+    let phi = config.latitude.to_radians();
+    let declination =
+        23.45_f64.to_radians() * (360.0_f64.to_radians() * (284.0 + day_of_year) / 365.0).sin();
+
+    // clamp for polar day/night, where the argument would otherwise leave [-1, 1]
+    let cos_hour_angle = (-phi.tan() * declination.tan()).clamp(-1.0, 1.0);
+    let hour_angle_deg = cos_hour_angle.acos().to_degrees();
+
+    let sunrise_solar_hours = 12.0 - hour_angle_deg / 15.0;
+    let sunset_solar_hours = 12.0 + hour_angle_deg / 15.0;
+
+    // correct solar time (relative to the local meridian) to UTC using longitude
+    let sunrise_utc_hours = sunrise_solar_hours - config.longitude / 15.0;
+    let sunset_utc_hours = sunset_solar_hours - config.longitude / 15.0;
+
+    let sunrise = utc_hours_to_local(date, sunrise_utc_hours, &config.timezone);
+    let sunset = utc_hours_to_local(date, sunset_utc_hours, &config.timezone);
+    (sunrise, sunset)
+}
+
+fn utc_hours_to_local(date: chrono::NaiveDate, utc_hours: f64, tz: &Tz) -> DateTime<Tz> {
+    let seconds = (utc_hours * 3600.0).round() as i64;
+    let naive = date.and_hms_opt(0, 0, 0).unwrap() + Duration::seconds(seconds);
+    Utc.from_utc_datetime(&naive).with_timezone(tz)
+}
+
+/// Linearly interpolates the brightness percentage within `transition`
+/// windows around sunrise/sunset, and holds the day/night percentage
+/// outside of them.
+fn target_brightness_percent(
+    now: DateTime<Tz>,
+    sunrise: DateTime<Tz>,
+    sunset: DateTime<Tz>,
+    transition: Duration,
+    day_percent: u32,
+    night_percent: u32,
+) -> u32 {
+    let sunrise_start = sunrise - transition;
+    let sunrise_end = sunrise + transition;
+    let sunset_start = sunset - transition;
+    let sunset_end = sunset + transition;
+
+    if now < sunrise_start || now > sunset_end {
+        night_percent
+    } else if now < sunrise_end {
+        lerp_percent(now, sunrise_start, sunrise_end, night_percent, day_percent)
+    } else if now < sunset_start {
+        day_percent
+    } else {
+        lerp_percent(now, sunset_start, sunset_end, day_percent, night_percent)
+    }
+}
+
+fn lerp_percent(
+    now: DateTime<Tz>,
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
+    from_percent: u32,
+    to_percent: u32,
+) -> u32 {
+    let span = (end - start).num_milliseconds() as f64;
+    let elapsed = (now - start).num_milliseconds() as f64;
+    let t = (elapsed / span).clamp(0.0, 1.0);
+    (from_percent as f64 + (to_percent as f64 - from_percent as f64) * t).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn utc_config(latitude: f64, longitude: f64) -> DaemonConfig {
+        DaemonConfig {
+            latitude,
+            longitude,
+            timezone: Tz::UTC,
+            day_percent: 100,
+            night_percent: 20,
+            transition: Duration::minutes(30),
+            poll_interval: StdDuration::from_secs(60),
+        }
+    }
+
+    /// At the equator on the spring equinox day and length (sunrise/sunset)
+    /// should be close to 06:00/18:00 UTC, within the approximation's slack.
+    #[test]
+    fn solar_times_equator_equinox() {
+        let config = utc_config(0.0, 0.0);
+        let date = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        let (sunrise, sunset) = solar_times(&config, date);
+
+        let expected_sunrise = Utc.with_ymd_and_hms(2024, 3, 20, 6, 0, 0).unwrap();
+        let expected_sunset = Utc.with_ymd_and_hms(2024, 3, 20, 18, 0, 0).unwrap();
+        assert!((sunrise.with_timezone(&Utc) - expected_sunrise).num_minutes().abs() <= 20);
+        assert!((sunset.with_timezone(&Utc) - expected_sunset).num_minutes().abs() <= 20);
+    }
+
+    /// Near the poles during local summer the hour-angle argument leaves
+    /// [-1, 1] and gets clamped, giving a day length close to 24h (polar day).
+    #[test]
+    fn solar_times_polar_day_clamps_to_almost_24h() {
+        let config = utc_config(80.0, 0.0);
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let (sunrise, sunset) = solar_times(&config, date);
+
+        assert!((sunset - sunrise).num_hours() >= 23);
+    }
+
+    #[test]
+    fn lerp_percent_interpolates_linearly() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 6, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let end = Utc.with_ymd_and_hms(2024, 1, 1, 6, 30, 0).unwrap().with_timezone(&Tz::UTC);
+        let mid = Utc.with_ymd_and_hms(2024, 1, 1, 6, 15, 0).unwrap().with_timezone(&Tz::UTC);
+
+        assert_eq!(lerp_percent(start, start, end, 20, 100), 20);
+        assert_eq!(lerp_percent(end, start, end, 20, 100), 100);
+        assert_eq!(lerp_percent(mid, start, end, 20, 100), 60);
+    }
+
+    #[test]
+    fn target_brightness_percent_holds_outside_transition_windows() {
+        let sunrise = Utc.with_ymd_and_hms(2024, 1, 1, 7, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let sunset = Utc.with_ymd_and_hms(2024, 1, 1, 17, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let transition = Duration::minutes(30);
+
+        let before_dawn = sunrise - Duration::hours(1);
+        let midday = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let after_dusk = sunset + Duration::hours(1);
+
+        assert_eq!(target_brightness_percent(before_dawn, sunrise, sunset, transition, 100, 20), 20);
+        assert_eq!(target_brightness_percent(midday, sunrise, sunset, transition, 100, 20), 100);
+        assert_eq!(target_brightness_percent(after_dusk, sunrise, sunset, transition, 100, 20), 20);
+    }
+
+    #[test]
+    fn target_brightness_percent_interpolates_within_sunrise_window() {
+        let sunrise = Utc.with_ymd_and_hms(2024, 1, 1, 7, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let sunset = Utc.with_ymd_and_hms(2024, 1, 1, 17, 0, 0).unwrap().with_timezone(&Tz::UTC);
+        let transition = Duration::minutes(30);
+        let at_sunrise = sunrise;
+
+        let percent = target_brightness_percent(at_sunrise, sunrise, sunset, transition, 100, 20);
+        assert_eq!(percent, 60);
+    }
+}
@@ -0,0 +1,59 @@
+use std::error;
+use std::thread;
+use std::time::Duration;
+
+use clap::ArgEnum;
+
+use crate::backend::Backend;
+
+/// Interpolation curve used while fading between two backlight values.
+#[derive(Copy, Debug, Clone, PartialEq, Eq, ArgEnum)]
+pub enum Easing {
+    Linear,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            // smoothstep
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Ramps the backlight from `from` to `to` over `duration`, issuing one
+/// write every `timeslice`. The final write always lands exactly on `to`,
+/// regardless of rounding in the intermediate steps.
+pub fn fade_to(
+    backend: &dyn Backend,
+    from: u32,
+    to: u32,
+    duration: Duration,
+    timeslice: Duration,
+    easing: Easing,
+) -> Result<(), Box<dyn error::Error>> {
+    if from == to || duration.is_zero() || timeslice.is_zero() {
+        return backend.set(to);
+    }
+
+    let steps = (duration.as_secs_f64() / timeslice.as_secs_f64())
+        .ceil()
+        .max(1.0) as u64;
+
+    for step in 1..=steps {
+        let val = if step == steps {
+            to
+        } else {
+            let t = easing.apply(step as f64 / steps as f64);
+            (from as f64 + (to as f64 - from as f64) * t).round() as u32
+        };
+        backend.set(val)?;
+
+        if step != steps {
+            thread::sleep(timeslice);
+        }
+    }
+    Ok(())
+}
@@ -2,22 +2,39 @@ use clap::Result;
 use clap::{ArgEnum, Parser};
 use notify_rust::{Hint, Notification};
 use std::error;
-use xcb::randr;
-use xcb::x;
 
+mod backend;
+mod config;
 mod custom_errors;
+mod daemon;
+mod fade;
+mod sysfs_backend;
+mod xcb_backend;
+
+use backend::Backend;
 
 // TODO: what happens when the min_backlight value from xcb is not 0? -> is this even possible?
 // TODO: function documentation
 // TODO: test on more systems
 
 #[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
-enum Mode {
+pub(crate) enum Mode {
     Absolute,
     Relative,
     Step,
 }
 
+/// Selects which backend is used to query and change the backlight.
+/// `Auto` tries the XCB/RandR backend first and falls back to sysfs if it
+/// is unavailable (e.g. on Wayland/TTY or when RandR exposes no backlight
+/// property).
+#[derive(Copy, Debug, Clone, PartialEq, Eq, ArgEnum)]
+enum BackendKind {
+    Auto,
+    Xcb,
+    Sysfs,
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -67,6 +84,77 @@ struct Args {
     /// Specifies the title string of the notification. If not set, the title of the notification is the apps's name.
     #[clap(short, long)]
     title: Option<String>,
+
+    /// Backend used to query/change the backlight. 'auto' prefers XCB/RandR
+    /// and falls back to sysfs when it is unavailable.
+    #[clap(arg_enum, long, default_value_t = BackendKind::Auto)]
+    backend: BackendKind,
+
+    /// Name of the output to target (as reported by xrandr), e.g. 'eDP-1'.
+    /// Only applies to the XCB backend. Ignored when combined with --all.
+    #[clap(long)]
+    output: Option<String>,
+
+    /// Apply 'set'/'inc'/'dec' to every backlight-capable output instead of
+    /// just the first one found. Only applies to the XCB backend.
+    #[clap(long)]
+    all: bool,
+
+    /// Run as a long-lived daemon that shifts brightness between
+    /// --day-brightness and --night-brightness around local sunrise/sunset,
+    /// instead of performing a single get/set.
+    #[clap(long)]
+    daemon: bool,
+
+    /// Observer latitude in degrees, used to compute sunrise/sunset. Required for --daemon.
+    #[clap(long)]
+    latitude: Option<f64>,
+
+    /// Observer longitude in degrees, used to compute sunrise/sunset. Required for --daemon.
+    #[clap(long)]
+    longitude: Option<f64>,
+
+    /// IANA timezone name (e.g. "Europe/Berlin") the daemon's clock runs in. Required for --daemon.
+    #[clap(long)]
+    timezone: Option<String>,
+
+    /// Daytime brightness percentage used by --daemon.
+    #[clap(long, default_value_t = 100)]
+    day_brightness: u32,
+
+    /// Nighttime brightness percentage used by --daemon.
+    #[clap(long, default_value_t = 20)]
+    night_brightness: u32,
+
+    /// Minutes before/after sunrise and sunset during which --daemon linearly
+    /// fades between the day and night brightness.
+    #[clap(long, default_value_t = 30)]
+    transition_minutes: i64,
+
+    /// Seconds between brightness updates in --daemon mode.
+    #[clap(long, default_value_t = 60)]
+    poll_interval_secs: u64,
+
+    /// Smoothly ramp 'set'/'inc'/'dec' to their target over this many
+    /// milliseconds instead of jumping instantaneously. Combined with
+    /// --all, each output fades one after another rather than in
+    /// parallel, so total wall-clock time is roughly `fade * num_outputs`.
+    #[clap(long)]
+    fade: Option<u64>,
+
+    /// Duration in milliseconds of a single fade step. Widen this on slower
+    /// machines that can't keep up with the default ~60 steps/sec.
+    #[clap(long, default_value_t = 16)]
+    fade_timeslice_ms: u64,
+
+    /// Interpolation curve used while fading.
+    #[clap(arg_enum, long, default_value_t = fade::Easing::Linear)]
+    fade_easing: fade::Easing,
+
+    /// Apply a named brightness preset/alias from the config file instead of
+    /// passing --set/mode directly. Overrides 'mode' and '--set'.
+    #[clap(long)]
+    preset: Option<String>,
 }
 
 const APPNAME: &str = env!("CARGO_PKG_NAME");
@@ -84,26 +172,95 @@ fn main() -> Result<(), String> {
 }
 
 fn handle_backlight() -> Result<(), Box<dyn error::Error>> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    apply_preset(&mut args)?;
 
-    let (conn, output) = init_bus_connection()?;
+    let backends = resolve_backends(&args)?;
 
-    let backlight_atom = query_backlight_atom(&conn)?;
+    if args.daemon {
+        // daemon mode drives a single output on an ongoing basis, so --all is not supported here
+        let backend = backends.first().unwrap();
+        let max_backlight = backend.max()?;
+        let config = build_daemon_config(&args)?;
+        return daemon::run(backend.as_ref(), max_backlight, config);
+    }
 
-    // we assume that the min backlight is always 0
-    let (_, max_backlight) = query_min_max_backlight_values(&conn, output, backlight_atom)?;
+    // backends are driven one after another, not concurrently, so with
+    // --all --fade the total wall-clock time is `fade * backends.len()`
+    for backend in &backends {
+        handle_backlight_for(backend.as_ref(), &args)?;
+    }
+    Ok(())
+}
+
+/// If `--preset NAME` was given, resolves it against the config file and
+/// overrides `mode`/`set` with its value, as if they had been passed
+/// directly on the command line.
+fn apply_preset(args: &mut Args) -> Result<(), Box<dyn error::Error>> {
+    let preset_name = match &args.preset {
+        Some(name) => name.clone(),
+        None => return Ok(()),
+    };
+
+    let config = config::Config::load()?;
+    let preset_value = config
+        .resolve(&preset_name)
+        .ok_or_else(|| custom_errors::PresetNotFoundError {
+            name: preset_name.clone(),
+        })?;
+    let (mode, val) = config::parse_preset_value(preset_value)?;
+
+    args.mode = mode;
+    args.set = Some(val);
+    Ok(())
+}
+
+fn build_daemon_config(args: &Args) -> Result<daemon::DaemonConfig, Box<dyn error::Error>> {
+    // clap's `required_if_eq` does not fire against a plain bool flag, so
+    // --latitude/--longitude/--timezone have to be validated by hand here
+    let (latitude, longitude, timezone) =
+        match (&args.latitude, &args.longitude, &args.timezone) {
+            (Some(latitude), Some(longitude), Some(timezone)) => (*latitude, *longitude, timezone),
+            _ => return Err(Box::new(custom_errors::MissingDaemonArgumentsError)),
+        };
+    let timezone = daemon::DaemonConfig::parse_timezone(timezone)?;
+
+    if !(0..=100).contains(&args.day_brightness) {
+        return Err(Box::new(custom_errors::DaemonBrightnessOutOfRangeError {
+            name: "day-brightness",
+            value: args.day_brightness,
+        }));
+    }
+    if !(0..=100).contains(&args.night_brightness) {
+        return Err(Box::new(custom_errors::DaemonBrightnessOutOfRangeError {
+            name: "night-brightness",
+            value: args.night_brightness,
+        }));
+    }
+
+    Ok(daemon::DaemonConfig {
+        latitude,
+        longitude,
+        timezone,
+        day_percent: args.day_brightness,
+        night_percent: args.night_brightness,
+        transition: chrono::Duration::minutes(args.transition_minutes),
+        poll_interval: std::time::Duration::from_secs(args.poll_interval_secs),
+    })
+}
+
+fn handle_backlight_for(backend: &dyn Backend, args: &Args) -> Result<(), Box<dyn error::Error>> {
+    let max_backlight = backend.max()?;
 
     match args.mode {
         // ABSOLUTE MODE
         Mode::Absolute => {
             handle_backlight_requests(
-                &conn,
-                output,
-                backlight_atom,
+                backend,
                 max_backlight,
                 0,
                 max_backlight,
-                &args,
+                args,
                 &identity,
                 &identity,
             )?;
@@ -113,13 +270,11 @@ fn handle_backlight() -> Result<(), Box<dyn error::Error>> {
         // RELATIVE MODE
         Mode::Relative => {
             handle_backlight_requests(
-                &conn,
-                output,
-                backlight_atom,
+                backend,
                 100,
                 0,
                 max_backlight,
-                &args,
+                args,
                 &absolute_to_steps,
                 &steps_to_absolute,
             )?;
@@ -138,13 +293,11 @@ fn handle_backlight() -> Result<(), Box<dyn error::Error>> {
                 }));
             } else {
                 handle_backlight_requests(
-                    &conn,
-                    output,
-                    backlight_atom,
+                    backend,
                     steps,
                     0,
                     max_backlight,
-                    &args,
+                    args,
                     &absolute_to_steps,
                     &steps_to_absolute,
                 )?;
@@ -154,96 +307,51 @@ fn handle_backlight() -> Result<(), Box<dyn error::Error>> {
     }
 }
 
-fn init_bus_connection() -> Result<(xcb::Connection, xcb::randr::Output), Box<dyn error::Error>> {
-    let (conn, screen_num) = xcb::Connection::connect(None)?;
-    let setup = conn.get_setup();
-    let screen = setup.roots().nth(screen_num as usize).unwrap();
-    let root_window = screen.root();
-    let curr_screen_res =
-        conn.wait_for_reply(conn.send_request(&randr::GetScreenResourcesCurrent {
-            window: root_window,
-        }))?;
-
-    if curr_screen_res.outputs().len() > 0 {
-        let curr_output = curr_screen_res.outputs()[0];
-        return Ok((conn, curr_output));
-    } else {
-        return Err(Box::new(custom_errors::NoValidScreenResourceError));
+/// Builds the backend(s) selected via `--backend`/`--output`/`--all`. In
+/// `Auto` mode the XCB/RandR backend is tried first and sysfs is only used
+/// as a fallback, since RandR may be reachable but simply not expose a
+/// backlight property on any output (e.g. no valid atom or value range).
+///
+/// `--all` returns every backlight-capable RandR output; otherwise a single
+/// output is returned, either the one named by `--output` or the first
+/// capable one found. `--output`/`--all` only apply to the XCB backend.
+fn resolve_backends(args: &Args) -> Result<Vec<Box<dyn Backend>>, Box<dyn error::Error>> {
+    match args.backend {
+        BackendKind::Xcb => select_xcb_backends(args),
+        BackendKind::Sysfs => Ok(vec![Box::new(sysfs_backend::SysfsBackend::discover()?)]),
+        BackendKind::Auto => match select_xcb_backends(args) {
+            Ok(backends) => Ok(backends),
+            // an explicit --output that doesn't resolve is a user error, not a
+            // reason to silently fall back to whatever sysfs picks first
+            Err(e) if e.is::<custom_errors::OutputNotFoundError>() => Err(e),
+            Err(_) => Ok(vec![Box::new(sysfs_backend::SysfsBackend::discover()?)]),
+        },
     }
 }
 
-fn query_backlight_atom(conn: &xcb::Connection) -> Result<xcb::x::Atom, Box<dyn error::Error>> {
-    // check for 'Backlight' or 'BACKLIGHT' property
-    // we also cannot recover from this error
-    let atom_result = conn.wait_for_reply(conn.send_request(&x::InternAtom {
-        only_if_exists: true,
-        name: b"BACKLIGHT",
-    }));
-
-    // we want to recover from this error since the atom maybe written in another way
+fn select_xcb_backends(args: &Args) -> Result<Vec<Box<dyn Backend>>, Box<dyn error::Error>> {
+    let mut outputs = xcb_backend::discover()?;
 
-    match atom_result {
-        Ok(atom) => {
-            return Ok(atom.atom());
-        }
-        Err(e) => {
-            eprintln!("{:?}", e);
-            let atom_result = conn.wait_for_reply(conn.send_request(&x::InternAtom {
-                only_if_exists: true,
-                name: b"Backlight",
-            }))?;
-            return Ok(atom_result.atom());
-        }
+    if args.all {
+        return Ok(outputs
+            .into_iter()
+            .map(|o| Box::new(o) as Box<dyn Backend>)
+            .collect());
     }
-}
 
-fn query_min_max_backlight_values(
-    conn: &xcb::Connection,
-    output: xcb::randr::Output,
-    backlight_atom: xcb::x::Atom,
-) -> Result<(u32, u32), Box<dyn error::Error>> {
-    let valid_val = conn.wait_for_reply(conn.send_request(&randr::QueryOutputProperty {
-        output,
-        property: backlight_atom,
-    }))?;
-
-    // check validity of returned values
-    // response type == 1 seems to be the proper response the query output property request
-    if valid_val.response_type() == 1 && valid_val.range() && valid_val.valid_values().len() == 2 {
-        let min_backlight_value = valid_val.valid_values()[0];
-        let max_backlight_value = valid_val.valid_values()[1];
-        return Ok((min_backlight_value as u32, max_backlight_value as u32));
-    } else {
-        return Err(Box::new(custom_errors::NoValidBacklightRangeValuesError));
+    if let Some(name) = &args.output {
+        let idx = outputs
+            .iter()
+            .position(|o| &o.name == name)
+            .ok_or_else(|| custom_errors::OutputNotFoundError { name: name.clone() })?;
+        return Ok(vec![Box::new(outputs.remove(idx))]);
     }
-}
 
-fn query_current_backlight_value(
-    conn: &xcb::Connection,
-    output: xcb::randr::Output,
-    backlight_atom: xcb::x::Atom,
-) -> Result<u32, Box<dyn error::Error>> {
-    let output_property = conn.wait_for_reply(conn.send_request(&randr::GetOutputProperty {
-        output,
-        property: backlight_atom,
-        r#type: x::ATOM_INTEGER,
-        long_offset: 0,
-        long_length: 4,
-        delete: false,
-        pending: false,
-    }))?;
-
-    if output_property.response_type() == 1 && output_property.data::<u32>().len() == 1 {
-        return Ok(output_property.data::<u32>()[0]);
-    } else {
-        return Err(Box::new(custom_errors::NoValidCurrenBacklightValueError));
-    }
+    Ok(vec![Box::new(outputs.remove(0))])
 }
 
 fn handle_backlight_requests(
-    conn: &xcb::Connection,
-    output: xcb::randr::Output,
-    backlight_atom: xcb::x::Atom,
+    backend: &dyn Backend,
     max_val: u32,
     min_val: u32,
     max_backlight: u32,
@@ -262,7 +370,7 @@ fn handle_backlight_requests(
 
     // HANDLE GET COMMAND
     if args.get == true {
-        let curr_backlight = query_current_backlight_value(&conn, output, backlight_atom)?;
+        let curr_backlight = backend.current()?;
         let val_step = to_step(max_backlight, max_val, curr_backlight);
         if let Some(pretty_output) = &args.pretty_format {
             let pretty_out = format_output(min_val, max_val, val_step, pretty_output.to_string());
@@ -286,7 +394,7 @@ fn handle_backlight_requests(
     // HANDLE INC COMMAND
     } else if let Some(inc_val) = args.inc {
         // calculate new to be increased backlight val
-        let curr_backlight = query_current_backlight_value(&conn, output, backlight_atom)?;
+        let curr_backlight = backend.current()?;
         let val_step = to_step(max_backlight, max_val, curr_backlight);
         let new_backlight_val = if ((val_step as i32) + (inc_val as i32)) > max_val as i32 {
             max_val
@@ -297,8 +405,7 @@ fn handle_backlight_requests(
         // set increased backlight val
         if valid_backlight_range.contains(&new_backlight_val) {
             let val = from_step(max_backlight, max_val, new_backlight_val);
-            request_backlight_value_change(val, &conn, output, backlight_atom)?;
-            send_notification(max_backlight, val, notification_title)?;
+            apply_change(backend, curr_backlight, val, max_backlight, args, notification_title)?;
             return Ok(());
         } else {
             return Err(Box::new(custom_errors::ValueOutOfRangeError {
@@ -311,7 +418,7 @@ fn handle_backlight_requests(
     // HANDLE DEC COMMAND
     } else if let Some(dec_val) = args.dec {
         // calculate new to be decreased backlight val
-        let curr_backlight = query_current_backlight_value(&conn, output, backlight_atom)?;
+        let curr_backlight = backend.current()?;
         let val_step = to_step(max_backlight, max_val, curr_backlight);
         let new_backlight_val = if ((val_step as i32) - (dec_val as i32)) < min_val as i32 {
             min_val
@@ -322,8 +429,7 @@ fn handle_backlight_requests(
         // set decreased backlight val
         if valid_backlight_range.contains(&new_backlight_val) {
             let val = from_step(max_backlight, max_val, new_backlight_val);
-            request_backlight_value_change(val, &conn, output, backlight_atom)?;
-            send_notification(max_backlight, val, notification_title)?;
+            apply_change(backend, curr_backlight, val, max_backlight, args, notification_title)?;
             return Ok(());
         } else {
             return Err(Box::new(custom_errors::ValueOutOfRangeError {
@@ -336,9 +442,9 @@ fn handle_backlight_requests(
     // HANDLE SET COMMAND
     } else if let Some(val_step) = args.set {
         if valid_backlight_range.contains(&val_step) {
+            let curr_backlight = backend.current()?;
             let val = from_step(max_backlight, max_val, val_step);
-            request_backlight_value_change(val, &conn, output, backlight_atom)?;
-            send_notification(max_backlight, val, notification_title)?;
+            apply_change(backend, curr_backlight, val, max_backlight, args, notification_title)?;
             return Ok(());
         } else {
             return Err(Box::new(custom_errors::ValueOutOfRangeError {
@@ -351,7 +457,7 @@ fn handle_backlight_requests(
     // HANDLE CASE OF NO COMMANDS
     } else {
         // if no arguments/ options are provided, just print out the current absolute value
-        let curr_backlight = query_current_backlight_value(&conn, output, backlight_atom)?;
+        let curr_backlight = backend.current()?;
         let val_step = to_step(max_backlight, max_val, curr_backlight);
         if let Some(pretty_output) = &args.pretty_format {
             let pretty_out = format_output(min_val, max_val, val_step, pretty_output.to_string());
@@ -364,20 +470,34 @@ fn handle_backlight_requests(
     }
 }
 
-fn request_backlight_value_change(
-    val: u32,
-    conn: &xcb::Connection,
-    output: xcb::randr::Output,
-    backlight_atom: xcb::x::Atom,
+/// Moves the backlight from `from_val` to `to_val`, either instantly or, if
+/// `--fade` is set, smoothly over that many milliseconds. If
+/// `--notifications` is set, only one notification is sent, once the
+/// target value has been reached.
+fn apply_change(
+    backend: &dyn Backend,
+    from_val: u32,
+    to_val: u32,
+    max_backlight: u32,
+    args: &Args,
+    notification_title: &str,
 ) -> Result<(), Box<dyn error::Error>> {
-    conn.check_request(conn.send_request_checked(&randr::ChangeOutputProperty {
-        output,
-        property: backlight_atom,
-        mode: x::PropMode::Replace,
-        data: &[val],
-        r#type: x::ATOM_INTEGER,
-    }))?;
-    return Ok(());
+    if let Some(fade_ms) = args.fade {
+        fade::fade_to(
+            backend,
+            from_val,
+            to_val,
+            std::time::Duration::from_millis(fade_ms),
+            std::time::Duration::from_millis(args.fade_timeslice_ms),
+            args.fade_easing,
+        )?;
+    } else {
+        backend.set(to_val)?;
+    }
+    if args.notifications {
+        send_notification(max_backlight, to_val, notification_title)?;
+    }
+    Ok(())
 }
 
 fn format_output(min: u32, max: u32, val: u32, format: String) -> String {
@@ -393,7 +513,7 @@ fn absolute_to_steps(max: u32, step: u32, val: u32) -> u32 {
     return rslt.round() as u32;
 }
 
-fn steps_to_absolute(max: u32, steps: u32, val: u32) -> u32 {
+pub(crate) fn steps_to_absolute(max: u32, steps: u32, val: u32) -> u32 {
     let rslt = (max as f32 / steps as f32) * val as f32;
     return rslt.round() as u32;
 }
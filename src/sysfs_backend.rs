@@ -0,0 +1,62 @@
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rustix::fs::{open, Mode, OFlags};
+use rustix::io::{read, write};
+
+use crate::backend::Backend;
+use crate::custom_errors;
+
+const BACKLIGHT_CLASS_DIR: &str = "/sys/class/backlight";
+
+/// Drives the backlight of a sysfs device under `/sys/class/backlight/<device>`.
+/// Reading `brightness`/`max_brightness` works unprivileged, but writing
+/// `brightness` typically requires either a udev rule granting the
+/// `video` group write access to the device, or running as root.
+pub struct SysfsBackend {
+    device_dir: PathBuf,
+}
+
+impl SysfsBackend {
+    /// Picks the first device exposed under `/sys/class/backlight`.
+    pub fn discover() -> Result<Self, Box<dyn error::Error>> {
+        let mut entries = fs::read_dir(BACKLIGHT_CLASS_DIR)?;
+        match entries.next() {
+            Some(entry) => Ok(SysfsBackend {
+                device_dir: entry?.path(),
+            }),
+            None => Err(Box::new(custom_errors::NoBacklightDeviceFoundError)),
+        }
+    }
+}
+
+impl Backend for SysfsBackend {
+    fn max(&self) -> Result<u32, Box<dyn error::Error>> {
+        read_u32_file(&self.device_dir.join("max_brightness"))
+    }
+
+    fn current(&self) -> Result<u32, Box<dyn error::Error>> {
+        read_u32_file(&self.device_dir.join("brightness"))
+    }
+
+    fn set(&self, val: u32) -> Result<(), Box<dyn error::Error>> {
+        write_u32_file(&self.device_dir.join("brightness"), val)
+    }
+}
+
+/// Reads an integer out of a sysfs file via raw open/read/close syscalls.
+fn read_u32_file(path: &Path) -> Result<u32, Box<dyn error::Error>> {
+    let fd = open(path, OFlags::RDONLY, Mode::empty())?;
+    let mut buf = [0u8; 32];
+    let n = read(&fd, &mut buf)?;
+    let text = std::str::from_utf8(&buf[..n])?.trim();
+    Ok(text.parse::<u32>()?)
+}
+
+/// Writes an integer into a sysfs file via raw open/write/close syscalls.
+fn write_u32_file(path: &Path, val: u32) -> Result<(), Box<dyn error::Error>> {
+    let fd = open(path, OFlags::WRONLY, Mode::empty())?;
+    write(&fd, val.to_string().as_bytes())?;
+    Ok(())
+}
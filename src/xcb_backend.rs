@@ -0,0 +1,193 @@
+use std::error;
+use std::rc::Rc;
+use xcb::randr;
+use xcb::x;
+
+use crate::backend::Backend;
+use crate::custom_errors;
+
+/// Drives the backlight of a single RandR output via the `BACKLIGHT`
+/// (or `Backlight`) output property.
+pub struct XcbBackend {
+    conn: Rc<xcb::Connection>,
+    output: xcb::randr::Output,
+    /// Name as reported by `GetOutputInfo`, e.g. "eDP-1". Used to resolve
+    /// `--output NAME`.
+    pub name: String,
+    backlight_atom: xcb::x::Atom,
+    // we assume that the min backlight is always 0
+    max_backlight: u32,
+}
+
+impl Backend for XcbBackend {
+    fn max(&self) -> Result<u32, Box<dyn error::Error>> {
+        Ok(self.max_backlight)
+    }
+
+    fn current(&self) -> Result<u32, Box<dyn error::Error>> {
+        query_current_backlight_value(&self.conn, self.output, self.backlight_atom)
+    }
+
+    fn set(&self, val: u32) -> Result<(), Box<dyn error::Error>> {
+        request_backlight_value_change(val, &self.conn, self.output, self.backlight_atom)
+    }
+}
+
+/// Connects to the X server and returns every output that exposes a valid
+/// backlight property, in the order reported by `GetScreenResourcesCurrent`.
+/// Outputs that fail the range or current-value validation (no backlight
+/// property, malformed range, wrong reply format/type) are silently
+/// skipped, so hybrid-GPU/multi-monitor setups only see the outputs that
+/// actually support dimming.
+pub fn discover() -> Result<Vec<XcbBackend>, Box<dyn error::Error>> {
+    let (conn, root_window) = connect_root()?;
+    let conn = Rc::new(conn);
+    let backlight_atom = query_backlight_atom(&conn)?;
+
+    let curr_screen_res =
+        conn.wait_for_reply(conn.send_request(&randr::GetScreenResourcesCurrent {
+            window: root_window,
+        }))?;
+
+    let mut backends = Vec::new();
+    for &output in curr_screen_res.outputs() {
+        if let Some(backend) = probe_output(&conn, output, backlight_atom) {
+            backends.push(backend);
+        }
+    }
+
+    if backends.is_empty() {
+        Err(Box::new(custom_errors::NoBacklightCapableOutputError))
+    } else {
+        Ok(backends)
+    }
+}
+
+/// Validates that `output` exposes a usable backlight range and current
+/// value, returning `None` instead of propagating an error so the caller
+/// can simply skip incapable outputs.
+fn probe_output(
+    conn: &Rc<xcb::Connection>,
+    output: xcb::randr::Output,
+    backlight_atom: xcb::x::Atom,
+) -> Option<XcbBackend> {
+    let (_, max_backlight) = query_min_max_backlight_values(conn, output, backlight_atom).ok()?;
+    query_current_backlight_value(conn, output, backlight_atom).ok()?;
+    let name = query_output_name(conn, output).ok()?;
+
+    Some(XcbBackend {
+        conn: Rc::clone(conn),
+        output,
+        name,
+        backlight_atom,
+        max_backlight,
+    })
+}
+
+fn connect_root() -> Result<(xcb::Connection, x::Window), Box<dyn error::Error>> {
+    let (conn, screen_num) = xcb::Connection::connect(None)?;
+    let setup = conn.get_setup();
+    let screen = setup.roots().nth(screen_num as usize).unwrap();
+    let root_window = screen.root();
+    Ok((conn, root_window))
+}
+
+fn query_backlight_atom(conn: &xcb::Connection) -> Result<xcb::x::Atom, Box<dyn error::Error>> {
+    // check for 'Backlight' or 'BACKLIGHT' property
+    // we also cannot recover from this error
+    let atom_result = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+        only_if_exists: true,
+        name: b"BACKLIGHT",
+    }));
+
+    // we want to recover from this error since the atom maybe written in another way
+
+    match atom_result {
+        Ok(atom) => {
+            return Ok(atom.atom());
+        }
+        Err(e) => {
+            eprintln!("{:?}", e);
+            let atom_result = conn.wait_for_reply(conn.send_request(&x::InternAtom {
+                only_if_exists: true,
+                name: b"Backlight",
+            }))?;
+            return Ok(atom_result.atom());
+        }
+    }
+}
+
+fn query_output_name(
+    conn: &xcb::Connection,
+    output: xcb::randr::Output,
+) -> Result<String, Box<dyn error::Error>> {
+    let info = conn.wait_for_reply(conn.send_request(&randr::GetOutputInfo {
+        output,
+        config_timestamp: x::CURRENT_TIME,
+    }))?;
+    Ok(String::from_utf8_lossy(info.name()).into_owned())
+}
+
+fn query_min_max_backlight_values(
+    conn: &xcb::Connection,
+    output: xcb::randr::Output,
+    backlight_atom: xcb::x::Atom,
+) -> Result<(u32, u32), Box<dyn error::Error>> {
+    let valid_val = conn.wait_for_reply(conn.send_request(&randr::QueryOutputProperty {
+        output,
+        property: backlight_atom,
+    }))?;
+
+    // check validity of returned values
+    // response type == 1 seems to be the proper response the query output property request
+    if valid_val.response_type() == 1 && valid_val.range() && valid_val.valid_values().len() == 2 {
+        let min_backlight_value = valid_val.valid_values()[0];
+        let max_backlight_value = valid_val.valid_values()[1];
+        return Ok((min_backlight_value as u32, max_backlight_value as u32));
+    } else {
+        return Err(Box::new(custom_errors::NoValidBacklightRangeValuesError));
+    }
+}
+
+fn query_current_backlight_value(
+    conn: &xcb::Connection,
+    output: xcb::randr::Output,
+    backlight_atom: xcb::x::Atom,
+) -> Result<u32, Box<dyn error::Error>> {
+    let output_property = conn.wait_for_reply(conn.send_request(&randr::GetOutputProperty {
+        output,
+        property: backlight_atom,
+        r#type: x::ATOM_INTEGER,
+        long_offset: 0,
+        long_length: 4,
+        delete: false,
+        pending: false,
+    }))?;
+
+    // require a 32-bit ATOM_INTEGER reply carrying exactly one item, so we
+    // don't mistake an unrelated or malformed property for a backlight value
+    if output_property.format() == 32
+        && output_property.r#type() == x::ATOM_INTEGER
+        && output_property.data::<u32>().len() == 1
+    {
+        return Ok(output_property.data::<u32>()[0]);
+    } else {
+        return Err(Box::new(custom_errors::NoValidCurrenBacklightValueError));
+    }
+}
+
+fn request_backlight_value_change(
+    val: u32,
+    conn: &xcb::Connection,
+    output: xcb::randr::Output,
+    backlight_atom: xcb::x::Atom,
+) -> Result<(), Box<dyn error::Error>> {
+    conn.check_request(conn.send_request_checked(&randr::ChangeOutputProperty {
+        output,
+        property: backlight_atom,
+        mode: x::PropMode::Replace,
+        data: &[val],
+        r#type: x::ATOM_INTEGER,
+    }))?;
+    return Ok(());
+}